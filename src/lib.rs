@@ -15,15 +15,51 @@
 #![feature(default_type_params)]
 #![feature(unboxed_closures)]
 
+use std::cmp;
 use std::collections::dlist::{
     DList,
 };
 use std::collections::ring_buf::{
     RingBuf,
 };
+use std::mem;
 use std::mem::{
     transmute,
 };
+use std::ptr;
+use std::rt::heap::{
+    allocate,
+    deallocate,
+    EMPTY,
+};
+
+/// Read a `T` out of `ptr` and immediately drop it. Used as drop glue for
+/// the value currently parked in a `Morphism`'s scratch buffer, since the
+/// buffer only knows the type of what it holds through the `Link` that put
+/// it there.
+unsafe fn drop_in_place<T>(ptr: *mut ()) {
+    let _ = ptr::read(ptr as *const T);
+}
+
+/// Round `n` up to the next multiple of `align` (`align` is always a
+/// power of two, per `mem::align_of`). Used to lay out a second scratch
+/// region right after the main one, e.g. for `Morphism::first`/`second`/
+/// `fanout`/`product`, which stash a side value alongside the value
+/// threaded through the spliced-in chain.
+#[inline]
+fn round_up(n: uint, align: uint) -> uint {
+    (n + align - 1) & !(align - 1)
+}
+
+/// One link of a `Morphism` chain: a type-erased closure that reads its
+/// argument out of the shared scratch buffer, computes its result, and
+/// writes the result back into the same buffer in place, plus the drop
+/// glue for the type it leaves behind, which the runner records as the
+/// buffer's live value between steps.
+struct Link<'a> {
+    apply: Box<Fn(*mut ()) -> () + 'a>,
+    drop_out: unsafe fn(*mut ()),
+}
 
 /// A suspended chain of closures that behave as a function from type
 /// `A` to type `B`.
@@ -31,8 +67,45 @@ use std::mem::{
 /// When `B = A` the parameter `B` can be omitted: `Morphism<'a, A>`
 /// is equivalent to `Morphism<'a, A, A>`.  This is convenient for
 /// providing annotations with `Morphism::new()`.
+///
+/// The chain is applied through a single scratch buffer sized to the
+/// largest intermediate type seen across `head`/`tail`/`then`, rather
+/// than boxing each intermediate value, so `run` performs no per-step
+/// heap allocation.
 pub struct Morphism<'a, A, B = A> {
-    mfns: DList<RingBuf<Box<Fn(*const ()) -> *const () + 'a>>>,
+    mfns: DList<RingBuf<Link<'a>>>,
+    max_size: uint,
+    max_align: uint,
+    entry_drop: unsafe fn(*mut ()),
+}
+
+/// A suspended chain of `FnMut` closures that behave as a function from
+/// type `A` to type `B`.
+///
+/// This is the stateful counterpart to `Morphism`: each step may carry
+/// and mutate its own state across invocations, at the cost of requiring
+/// `&mut self` to drive the chain.
+pub struct MorphismMut<'a, A, B = A> {
+    mfns: DList<RingBuf<Box<FnMut(*const ()) -> *const () + 'a>>>,
+}
+
+/// A suspended chain of fallible (Kleisli) closures of the form
+/// `A -> Result<B, E>`, sharing a single error type `E` across the whole
+/// chain.
+///
+/// Unlike `Morphism`, running the chain short-circuits: the moment any
+/// step returns `Err`, the remaining steps are skipped and that error is
+/// returned immediately, rather than threading `.and_then` calls through
+/// every `tail`.
+pub struct Kleisli<'a, A, B, E> {
+    mfns: DList<RingBuf<Box<Fn(*const ()) -> Result<*const (), *const ()> + 'a>>>,
+}
+
+/// The result of one iteration of a `loop_while`-driven trampoline: either
+/// more work to do (fed back in as the next input) or a final result.
+pub enum Step<A, B> {
+    More(A),
+    Done(B),
 }
 
 #[allow(dead_code)]
@@ -55,6 +128,58 @@ impl Morphism<'static, Void> {
                 mfns.push_back(RingBuf::new());
                 mfns
             },
+            max_size: mem::size_of::<A>(),
+            max_align: mem::align_of::<A>(),
+            entry_drop: drop_in_place::<A>,
+        }
+    }
+
+    /// Create the identity chain for a chain of `FnMut` closures.
+    ///
+    /// Unlike `new`, the resulting `MorphismMut` may carry closures that
+    /// close over mutable state (counters, accumulators, caches) since
+    /// each step is invoked through `call_mut` rather than `call`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let mut f = Morphism::new_mut::<uint>();
+    /// assert_eq!(f(42u), 42u);
+    /// ```
+    #[inline]
+    pub fn new_mut<'a, A>() -> MorphismMut<'a, A> {
+        MorphismMut {
+            mfns: {
+                let mut mfns = DList::new();
+                mfns.push_back(RingBuf::new());
+                mfns
+            },
+        }
+    }
+
+    /// Create the identity Kleisli chain for fallible closures sharing
+    /// error type `E`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let f = Morphism::new_kleisli::<uint, String>()
+    ///     .and_then(|x| if x > 0u { Ok(x - 1u) } else { Err("underflow".to_string()) });
+    /// assert_eq!(f(1u), Ok(0u));
+    /// assert_eq!(f(0u), Err("underflow".to_string()));
+    /// ```
+    #[inline]
+    pub fn new_kleisli<'a, A, E>() -> Kleisli<'a, A, A, E> {
+        Kleisli {
+            mfns: {
+                let mut mfns = DList::new();
+                mfns.push_back(RingBuf::new());
+                mfns
+            },
         }
     }
 }
@@ -81,24 +206,28 @@ impl<'a, B, C> Morphism<'a, B, C> {
     {
         match self {
             Morphism {
-                mut mfns
+                mut mfns,
+                max_size,
+                max_align,
+                ..
             }
             =>
             {
                 // assert!(!mfns.is_empty())
                 { // borrow mfns
                     let head = mfns.front_mut().unwrap();
-                    let g = box move |&:ptr: *const ()| { unsafe {
-                        transmute::<Box<B>, *const ()>(
-                            box f.call((
-                                *transmute::<*const (), Box<A>>(ptr)
-                            ,))
-                        )
+                    let g = box move |&:buf: *mut ()| { unsafe {
+                        let a = ptr::read(buf as *const A);
+                        let b = f.call((a,));
+                        ptr::write(buf as *mut B, b);
                     }};
-                    head.push_front(g);
+                    head.push_front(Link { apply: g, drop_out: drop_in_place::<B> });
                 }; // forget mfns
                 Morphism {
                     mfns: mfns,
+                    max_size: cmp::max(max_size, mem::size_of::<A>()),
+                    max_align: cmp::max(max_align, mem::align_of::<A>()),
+                    entry_drop: drop_in_place::<A>,
                 }
             },
         }
@@ -127,24 +256,28 @@ impl<'a, A, B> Morphism<'a, A, B> {
     {
         match self {
             Morphism {
-                mut mfns
+                mut mfns,
+                max_size,
+                max_align,
+                entry_drop,
             }
             =>
             {
                 // assert!(!mfns.is_empty())
                 { // borrow mfns
                     let tail = mfns.back_mut().unwrap();
-                    let g = box move |&:ptr: *const ()| { unsafe {
-                        transmute::<Box<C>, *const ()>(
-                            box f.call((
-                                *transmute::<*const (), Box<B>>(ptr)
-                            ,))
-                        )
+                    let g = box move |&:buf: *mut ()| { unsafe {
+                        let b = ptr::read(buf as *const B);
+                        let c = f.call((b,));
+                        ptr::write(buf as *mut C, c);
                     }};
-                    tail.push_back(g);
+                    tail.push_back(Link { apply: g, drop_out: drop_in_place::<C> });
                 }; // forget mfns
                 Morphism {
                     mfns: mfns,
+                    max_size: cmp::max(max_size, mem::size_of::<C>()),
+                    max_align: cmp::max(max_align, mem::align_of::<C>()),
+                    entry_drop: entry_drop,
                 }
             },
         }
@@ -178,12 +311,18 @@ impl<'a, A, B> Morphism<'a, A, B> {
         match self {
             Morphism {
                 mfns: mut lhs,
+                max_size: lsize,
+                max_align: lalign,
+                entry_drop,
             }
             =>
             {
                 match other {
                     Morphism {
                         mfns: rhs,
+                        max_size: rsize,
+                        max_align: ralign,
+                        ..
                     }
                     =>
                     {
@@ -192,6 +331,9 @@ impl<'a, A, B> Morphism<'a, A, B> {
                                 lhs.append(rhs);
                                 lhs
                             },
+                            max_size: cmp::max(lsize, rsize),
+                            max_align: cmp::max(lalign, ralign),
+                            entry_drop: entry_drop,
                         }
                     },
                 }
@@ -199,20 +341,404 @@ impl<'a, A, B> Morphism<'a, A, B> {
         }
     }
 
+    /// Act on the first component of a pair, passing the second component
+    /// through untouched.
+    ///
+    /// `self`'s own steps are spliced directly into the result's chain
+    /// (rather than calling through `self` as a nested `Morphism`), with
+    /// a step in front that peels `D` off into a second scratch region
+    /// and a step at the back that recombines it once `self`'s chain has
+    /// produced `B`. A panic inside `self`'s chain while `D` is parked in
+    /// that second region leaks `D` rather than running its destructor
+    /// (safe, just not ideal) since only the main slot's current value is
+    /// tracked for panic-drop.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let f = Morphism::new::<uint>().tail(|x| x + 1u).first::<bool>();
+    /// assert_eq!(f((41u, true)), (42u, true));
+    /// ```
+    #[inline]
+    pub fn first<D>(self) -> Morphism<'a, (A, D), (B, D)> {
+        let side_align = mem::align_of::<D>();
+        let main_size = cmp::max(self.max_size,
+            cmp::max(mem::size_of::<(A, D)>(), mem::size_of::<(B, D)>()));
+        let main_align = cmp::max(self.max_align,
+            cmp::max(mem::align_of::<(A, D)>(), mem::align_of::<(B, D)>()));
+        let side_off = round_up(main_size, side_align);
+
+        match self {
+            Morphism { mut mfns, .. } => {
+                { // borrow mfns
+                    let front = mfns.front_mut().unwrap();
+                    let split = box move |&:buf: *mut ()| { unsafe {
+                        let (a, d) = ptr::read(buf as *const (A, D));
+                        ptr::write(buf as *mut A, a);
+                        ptr::write((buf as *mut u8).offset(side_off as int) as *mut D, d);
+                    }};
+                    front.push_front(Link { apply: split, drop_out: drop_in_place::<A> });
+                } // forget mfns
+                { // borrow mfns
+                    let back = mfns.back_mut().unwrap();
+                    let recombine = box move |&:buf: *mut ()| { unsafe {
+                        let b = ptr::read(buf as *const B);
+                        let d = ptr::read((buf as *mut u8).offset(side_off as int) as *const D);
+                        ptr::write(buf as *mut (B, D), (b, d));
+                    }};
+                    back.push_back(Link { apply: recombine, drop_out: drop_in_place::<(B, D)> });
+                } // forget mfns
+                Morphism {
+                    mfns: mfns,
+                    max_size: side_off + mem::size_of::<D>(),
+                    max_align: cmp::max(main_align, side_align),
+                    entry_drop: drop_in_place::<(A, D)>,
+                }
+            },
+        }
+    }
+
+    /// Act on the second component of a pair, passing the first component
+    /// through untouched. See `first` for the splicing/panic-safety notes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let f = Morphism::new::<uint>().tail(|x| x + 1u).second::<bool>();
+    /// assert_eq!(f((true, 41u)), (true, 42u));
+    /// ```
+    #[inline]
+    pub fn second<D>(self) -> Morphism<'a, (D, A), (D, B)> {
+        let side_align = mem::align_of::<D>();
+        let main_size = cmp::max(self.max_size,
+            cmp::max(mem::size_of::<(D, A)>(), mem::size_of::<(D, B)>()));
+        let main_align = cmp::max(self.max_align,
+            cmp::max(mem::align_of::<(D, A)>(), mem::align_of::<(D, B)>()));
+        let side_off = round_up(main_size, side_align);
+
+        match self {
+            Morphism { mut mfns, .. } => {
+                { // borrow mfns
+                    let front = mfns.front_mut().unwrap();
+                    let split = box move |&:buf: *mut ()| { unsafe {
+                        let (d, a) = ptr::read(buf as *const (D, A));
+                        ptr::write(buf as *mut A, a);
+                        ptr::write((buf as *mut u8).offset(side_off as int) as *mut D, d);
+                    }};
+                    front.push_front(Link { apply: split, drop_out: drop_in_place::<A> });
+                } // forget mfns
+                { // borrow mfns
+                    let back = mfns.back_mut().unwrap();
+                    let recombine = box move |&:buf: *mut ()| { unsafe {
+                        let b = ptr::read(buf as *const B);
+                        let d = ptr::read((buf as *mut u8).offset(side_off as int) as *const D);
+                        ptr::write(buf as *mut (D, B), (d, b));
+                    }};
+                    back.push_back(Link { apply: recombine, drop_out: drop_in_place::<(D, B)> });
+                } // forget mfns
+                Morphism {
+                    mfns: mfns,
+                    max_size: side_off + mem::size_of::<D>(),
+                    max_align: cmp::max(main_align, side_align),
+                    entry_drop: drop_in_place::<(D, A)>,
+                }
+            },
+        }
+    }
+
+    /// Fan out a single argument to both `self` and `other`, pairing up
+    /// their results.
+    ///
+    /// `self`'s and `other`'s steps are spliced directly into the result's
+    /// chain: a step up front clones `A` into a side region, a step
+    /// between the two spliced chains stashes `self`'s result `B` into a
+    /// second side region and restores the cloned `A` into the main slot
+    /// for `other`'s chain, and a final step combines the stashed `B` with
+    /// `other`'s result `C`. As with `first`, a panic while a value sits
+    /// in a side region leaks it rather than double-dropping or reading
+    /// stale memory.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let f = Morphism::new::<uint>().tail(|x| x + 1u);
+    /// let g = Morphism::new::<uint>().tail(|x| x.to_string());
+    /// let h = f.fanout(g);
+    /// assert_eq!(h(41u), (42u, String::from_str("41")));
+    /// ```
+    #[inline]
+    pub fn fanout<C>(self, other: Morphism<'a, A, C>) -> Morphism<'a, A, (B, C)>
+        where
+        A: Clone,
+    {
+        let a_align = mem::align_of::<A>();
+        let b_align = mem::align_of::<B>();
+        let main_size = cmp::max(self.max_size, cmp::max(other.max_size, mem::size_of::<(B, C)>()));
+        let main_align = cmp::max(self.max_align, cmp::max(other.max_align, mem::align_of::<(B, C)>()));
+        let a_off = round_up(main_size, a_align);
+        let b_off = round_up(a_off + mem::size_of::<A>(), b_align);
+
+        match (self, other) {
+            (Morphism { mfns: mut lhs, .. }, Morphism { mfns: rhs, .. }) => {
+                { // borrow lhs
+                    let front = lhs.front_mut().unwrap();
+                    let stash_a = box move |&:buf: *mut ()| { unsafe {
+                        let a: &A = &*(buf as *const A);
+                        ptr::write((buf as *mut u8).offset(a_off as int) as *mut A, a.clone());
+                    }};
+                    front.push_front(Link { apply: stash_a, drop_out: drop_in_place::<A> });
+                } // forget lhs
+                { // borrow lhs
+                    let back = lhs.back_mut().unwrap();
+                    let swap = box move |&:buf: *mut ()| { unsafe {
+                        let b = ptr::read(buf as *const B);
+                        ptr::write((buf as *mut u8).offset(b_off as int) as *mut B, b);
+                        let a = ptr::read((buf as *mut u8).offset(a_off as int) as *const A);
+                        ptr::write(buf as *mut A, a);
+                    }};
+                    back.push_back(Link { apply: swap, drop_out: drop_in_place::<A> });
+                } // forget lhs
+                lhs.append(rhs);
+                { // borrow lhs
+                    let back = lhs.back_mut().unwrap();
+                    let combine = box move |&:buf: *mut ()| { unsafe {
+                        let c = ptr::read(buf as *const C);
+                        let b = ptr::read((buf as *mut u8).offset(b_off as int) as *const B);
+                        ptr::write(buf as *mut (B, C), (b, c));
+                    }};
+                    back.push_back(Link { apply: combine, drop_out: drop_in_place::<(B, C)> });
+                } // forget lhs
+                Morphism {
+                    mfns: lhs,
+                    max_size: b_off + mem::size_of::<B>(),
+                    max_align: cmp::max(main_align, cmp::max(a_align, b_align)),
+                    entry_drop: drop_in_place::<A>,
+                }
+            },
+        }
+    }
+
+    /// Run `self` and `other` side by side over the two halves of a pair.
+    ///
+    /// Like `first`, this splices `self`'s and `other`'s steps directly
+    /// into the result's chain rather than calling through either as a
+    /// nested `Morphism`: a step up front splits the pair and stashes `C`
+    /// in a side region, a step between the two spliced chains swaps the
+    /// main slot's `B` for the stashed `C`, and a final step recombines
+    /// `self`'s `B` with `other`'s `D`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let f = Morphism::new::<uint>().tail(|x| x + 1u);
+    /// let g = Morphism::new::<bool>().tail(|x: bool| !x);
+    /// let h = f.product(g);
+    /// assert_eq!(h((41u, true)), (42u, false));
+    /// ```
+    #[inline]
+    pub fn product<C, D>(self, other: Morphism<'a, C, D>) -> Morphism<'a, (A, C), (B, D)> {
+        let c_align = mem::align_of::<C>();
+        let b_align = mem::align_of::<B>();
+        let main_size = cmp::max(self.max_size,
+            cmp::max(other.max_size, cmp::max(mem::size_of::<(A, C)>(), mem::size_of::<(B, D)>())));
+        let main_align = cmp::max(self.max_align,
+            cmp::max(other.max_align, cmp::max(mem::align_of::<(A, C)>(), mem::align_of::<(B, D)>())));
+        let c_off = round_up(main_size, c_align);
+        let b_off = round_up(c_off + mem::size_of::<C>(), b_align);
+
+        match (self, other) {
+            (Morphism { mfns: mut lhs, .. }, Morphism { mfns: rhs, .. }) => {
+                { // borrow lhs
+                    let front = lhs.front_mut().unwrap();
+                    let split = box move |&:buf: *mut ()| { unsafe {
+                        let (a, c) = ptr::read(buf as *const (A, C));
+                        ptr::write(buf as *mut A, a);
+                        ptr::write((buf as *mut u8).offset(c_off as int) as *mut C, c);
+                    }};
+                    front.push_front(Link { apply: split, drop_out: drop_in_place::<A> });
+                } // forget lhs
+                { // borrow lhs
+                    let back = lhs.back_mut().unwrap();
+                    let swap = box move |&:buf: *mut ()| { unsafe {
+                        let b = ptr::read(buf as *const B);
+                        ptr::write((buf as *mut u8).offset(b_off as int) as *mut B, b);
+                        let c = ptr::read((buf as *mut u8).offset(c_off as int) as *const C);
+                        ptr::write(buf as *mut C, c);
+                    }};
+                    back.push_back(Link { apply: swap, drop_out: drop_in_place::<C> });
+                } // forget lhs
+                lhs.append(rhs);
+                { // borrow lhs
+                    let back = lhs.back_mut().unwrap();
+                    let combine = box move |&:buf: *mut ()| { unsafe {
+                        let d = ptr::read(buf as *const D);
+                        let b = ptr::read((buf as *mut u8).offset(b_off as int) as *const B);
+                        ptr::write(buf as *mut (B, D), (b, d));
+                    }};
+                    back.push_back(Link { apply: combine, drop_out: drop_in_place::<(B, D)> });
+                } // forget lhs
+                Morphism {
+                    mfns: lhs,
+                    max_size: b_off + mem::size_of::<B>(),
+                    max_align: cmp::max(main_align, cmp::max(c_align, b_align)),
+                    entry_drop: drop_in_place::<(A, C)>,
+                }
+            },
+        }
+    }
+
     /// Given an argument, run the chain of closures in a loop and return the
     /// final result.
+    ///
+    /// The argument and every intermediate value live in a single scratch
+    /// buffer sized to `self.max_size`/`self.max_align`, allocated once per
+    /// call; each step reads its input out of the buffer and writes its
+    /// output back in place, so no boxing happens per step. A guard tracks
+    /// the drop glue for whatever value currently lives in the buffer and
+    /// frees the buffer on unwind. The glue is cleared while a step runs:
+    /// a step has moved its input out of the buffer, so a panic inside it
+    /// drops that input once through the step's own unwinding and the
+    /// guard drops nothing.
     #[inline]
     fn run(&self, x: A) -> B { unsafe {
-        let mut res = transmute::<Box<A>, *const ()>(box x);
+        struct Scratch {
+            buf: *mut u8,
+            size: uint,
+            align: uint,
+            glue: Option<unsafe fn(*mut ())>,
+        }
+        impl Drop for Scratch {
+            fn drop(&mut self) {
+                unsafe {
+                    match self.glue {
+                        Some(glue) => glue(self.buf as *mut ()),
+                        None => {},
+                    }
+                    // `allocate`/`deallocate` don't guarantee zero-size
+                    // support; skip them the way `Box`/`Vec` do for ZSTs.
+                    if self.size != 0u {
+                        deallocate(self.buf, self.size, self.align);
+                    }
+                }
+            }
+        }
+
+        let mut scratch = Scratch {
+            buf: if self.max_size == 0u { EMPTY as *mut u8 } else { allocate(self.max_size, self.max_align) },
+            size: self.max_size,
+            align: self.max_align,
+            glue: None,
+        };
+
+        ptr::write(scratch.buf as *mut A, x);
+        scratch.glue = Some(self.entry_drop);
+
         for fns in self.mfns.iter() {
-            for f in fns.iter() {
-                res = f.call((res,));
+            for step in fns.iter() {
+                scratch.glue = None;
+                step.apply.call((scratch.buf as *mut (),));
+                scratch.glue = Some(step.drop_out);
             }
         }
-        *transmute::<*const (), Box<B>>(res)
+
+        scratch.glue = None;
+        ptr::read(scratch.buf as *const B)
     }}
 }
 
+/// Trampoline a `Morphism<'a, A, Step<A, B>>` into a `Morphism<'a, A, B>`
+/// by repeatedly re-applying `step` to whatever `Step::More(a)` it
+/// returns, until it returns `Step::Done(b)`.
+///
+/// Unlike a fixed-length chain built with `then`, the number of
+/// iterations is decided at runtime by the values flowing through `step`
+/// rather than at composition time, all within a flat `while` loop that
+/// never grows the native stack. This lets tail-recursive algorithms
+/// (iterate-to-fixpoint, state machines, Newton iterations) be expressed
+/// as `Morphism`s, potentially running many thousands of iterations.
+///
+/// To keep that cheap, `step`'s own chain is run directly against a
+/// single scratch buffer allocated once up front (the same scheme
+/// `Morphism::run` uses), rather than by calling `step` itself once per
+/// iteration, which would pay a fresh `allocate`/`deallocate` pair on
+/// every pass through the loop.
+///
+/// # Example
+///
+/// ```rust
+/// use morphism::{Morphism, Step, loop_while};
+///
+/// let count_to_zero = loop_while(
+///     Morphism::new::<uint>().tail(|x| if x == 0u { Step::Done(0u) } else { Step::More(x - 1u) })
+/// );
+/// assert_eq!(count_to_zero(5u), 0u);
+/// ```
+#[inline]
+pub fn loop_while<'a, A, B>(step: Morphism<'a, A, Step<A, B>>) -> Morphism<'a, A, B> {
+    Morphism::new::<A>().tail(move |x: A| { unsafe {
+        struct Scratch {
+            buf: *mut u8,
+            size: uint,
+            align: uint,
+            glue: Option<unsafe fn(*mut ())>,
+        }
+        impl Drop for Scratch {
+            fn drop(&mut self) {
+                unsafe {
+                    match self.glue {
+                        Some(glue) => glue(self.buf as *mut ()),
+                        None => {},
+                    }
+                    if self.size != 0u {
+                        deallocate(self.buf, self.size, self.align);
+                    }
+                }
+            }
+        }
+
+        let mut scratch = Scratch {
+            buf: if step.max_size == 0u {
+                EMPTY as *mut u8
+            } else {
+                allocate(step.max_size, step.max_align)
+            },
+            size: step.max_size,
+            align: step.max_align,
+            glue: None,
+        };
+
+        ptr::write(scratch.buf as *mut A, x);
+        scratch.glue = Some(step.entry_drop);
+
+        loop {
+            for fns in step.mfns.iter() {
+                for link in fns.iter() {
+                    scratch.glue = None;
+                    link.apply.call((scratch.buf as *mut (),));
+                    scratch.glue = Some(link.drop_out);
+                }
+            }
+
+            scratch.glue = None;
+            match ptr::read(scratch.buf as *const Step<A, B>) {
+                Step::Done(b) => return b,
+                Step::More(next) => {
+                    ptr::write(scratch.buf as *mut A, next);
+                    scratch.glue = Some(step.entry_drop);
+                },
+            }
+        }
+    }})
+}
+
 // NOTE: we can't implement this for FnOnce; see #18835
 impl<'a, A, B> Fn(A) -> B for Morphism<'a, A, B> {
     extern "rust-call" fn call(&self, (x,): (A,)) -> B {
@@ -220,10 +746,322 @@ impl<'a, A, B> Fn(A) -> B for Morphism<'a, A, B> {
     }
 }
 
+impl<'a, B, C> MorphismMut<'a, B, C> {
+    /// Attach an `FnMut` closure to the front of the closure chain. This
+    /// corresponds to closure composition at the domain (pre-composition).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let mut count = 0u;
+    /// let mut f = Morphism::new_mut::<uint>()
+    ///     .head_mut(|x: uint| { count += 1; x + count });
+    /// assert_eq!(f(0u), 1u);
+    /// assert_eq!(f(0u), 2u);
+    /// ```
+    #[inline]
+    pub fn head_mut<A, F: 'a>(self, f: F) -> MorphismMut<'a, A, C>
+        where
+        F: FnMut(A) -> B,
+    {
+        match self {
+            MorphismMut {
+                mut mfns
+            }
+            =>
+            {
+                // assert!(!mfns.is_empty())
+                { // borrow mfns
+                    let head = mfns.front_mut().unwrap();
+                    let g = box move |&mut: ptr: *const ()| { unsafe {
+                        transmute::<Box<B>, *const ()>(
+                            box f.call_mut((
+                                *transmute::<*const (), Box<A>>(ptr)
+                            ,))
+                        )
+                    }};
+                    head.push_front(g);
+                }; // forget mfns
+                MorphismMut {
+                    mfns: mfns,
+                }
+            },
+        }
+    }
+}
+
+impl<'a, A, B> MorphismMut<'a, A, B> {
+    /// Attach an `FnMut` closure to the back of the closure chain. This
+    /// corresponds to closure composition at the codomain (post-composition).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let mut total = 0u;
+    /// let mut f = Morphism::new_mut::<uint>()
+    ///     .tail_mut(|x: uint| { total += x; total });
+    /// assert_eq!(f(1u), 1u);
+    /// assert_eq!(f(2u), 3u);
+    /// ```
+    #[inline]
+    pub fn tail_mut<C, F: 'a>(self, f: F) -> MorphismMut<'a, A, C>
+        where
+        F: FnMut(B) -> C,
+    {
+        match self {
+            MorphismMut {
+                mut mfns
+            }
+            =>
+            {
+                // assert!(!mfns.is_empty())
+                { // borrow mfns
+                    let tail = mfns.back_mut().unwrap();
+                    let g = box move |&mut: ptr: *const ()| { unsafe {
+                        transmute::<Box<C>, *const ()>(
+                            box f.call_mut((
+                                *transmute::<*const (), Box<B>>(ptr)
+                            ,))
+                        )
+                    }};
+                    tail.push_back(g);
+                }; // forget mfns
+                MorphismMut {
+                    mfns: mfns,
+                }
+            },
+        }
+    }
+
+    /// Compose one `MorphismMut` with another.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let mut count = 0u;
+    /// let f = Morphism::new_mut::<uint>()
+    ///     .tail_mut(|x: uint| { count += 1; x + count });
+    ///
+    /// let mut total = 0u;
+    /// let g = Morphism::new_mut::<uint>()
+    ///     .tail_mut(|x: uint| { total += x; total });
+    ///
+    /// let mut h = f.then_mut(g);
+    /// assert_eq!(h(0u), 1u);
+    /// assert_eq!(h(0u), 3u);
+    /// ```
+    #[inline]
+    pub fn then_mut<C>(self, other: MorphismMut<'a, B, C>) -> MorphismMut<'a, A, C> {
+        match self {
+            MorphismMut {
+                mfns: mut lhs,
+            }
+            =>
+            {
+                match other {
+                    MorphismMut {
+                        mfns: rhs,
+                    }
+                    =>
+                    {
+                        MorphismMut {
+                            mfns: {
+                                lhs.append(rhs);
+                                lhs
+                            },
+                        }
+                    },
+                }
+            },
+        }
+    }
+
+    /// Given an argument, run the chain of closures in a loop and return the
+    /// final result. Since the stored closures are `FnMut`, driving the
+    /// chain requires unique access to `self`.
+    #[inline]
+    fn run(&mut self, x: A) -> B { unsafe {
+        let mut res = transmute::<Box<A>, *const ()>(box x);
+        for fns in self.mfns.iter_mut() {
+            for f in fns.iter_mut() {
+                res = f.call_mut((res,));
+            }
+        }
+        *transmute::<*const (), Box<B>>(res)
+    }}
+}
+
+impl<'a, A, B> FnMut(A) -> B for MorphismMut<'a, A, B> {
+    extern "rust-call" fn call_mut(&mut self, (x,): (A,)) -> B {
+        self.run(x)
+    }
+}
+
+impl<'a, A, B, E> Kleisli<'a, A, B, E> {
+    /// Attach a fallible closure to the back of the Kleisli chain. This
+    /// corresponds to Kleisli composition (`>=>`) in the `Result<_, E>`
+    /// monad.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use morphism::Morphism;
+    ///
+    /// let f = Morphism::new_kleisli::<uint, String>()
+    ///     .and_then(|x| Ok(x + 1u))
+    ///     .and_then(|x| if x < 10u { Ok(x) } else { Err("too big".to_string()) });
+    /// assert_eq!(f(1u), Ok(2u));
+    /// assert_eq!(f(9u), Err("too big".to_string()));
+    /// ```
+    #[inline]
+    pub fn and_then<C, F: 'a>(self, f: F) -> Kleisli<'a, A, C, E>
+        where
+        F: Fn(B) -> Result<C, E>,
+    {
+        match self {
+            Kleisli {
+                mut mfns
+            }
+            =>
+            {
+                // assert!(!mfns.is_empty())
+                { // borrow mfns
+                    let tail = mfns.back_mut().unwrap();
+                    let g = box move |&:ptr: *const ()| { unsafe {
+                        match f.call((
+                            *transmute::<*const (), Box<B>>(ptr)
+                        ,)) {
+                            Ok(c) => Ok(transmute::<Box<C>, *const ()>(box c)),
+                            Err(e) => Err(transmute::<Box<E>, *const ()>(box e)),
+                        }
+                    }};
+                    tail.push_back(g);
+                }; // forget mfns
+                Kleisli {
+                    mfns: mfns,
+                }
+            },
+        }
+    }
+
+    /// Given an argument, run the chain in a loop, short-circuiting on the
+    /// first `Err`.
+    ///
+    /// As soon as a step returns `Err`, the remaining `RingBuf`/`DList`
+    /// entries are skipped entirely and that error is returned, rather
+    /// than running the full chain the way `Morphism::run` does.
+    #[inline]
+    fn run(&self, x: A) -> Result<B, E> { unsafe {
+        let mut res: Result<*const (), *const ()> =
+            Ok(transmute::<Box<A>, *const ()>(box x));
+        'chain: for fns in self.mfns.iter() {
+            for f in fns.iter() {
+                match res {
+                    Ok(ptr) => { res = f.call((ptr,)); },
+                    Err(_) => break 'chain,
+                }
+            }
+        }
+        match res {
+            Ok(ptr) => Ok(*transmute::<*const (), Box<B>>(ptr)),
+            Err(ptr) => Err(*transmute::<*const (), Box<E>>(ptr)),
+        }
+    }}
+}
+
+impl<'a, A, B, E> Fn(A) -> Result<B, E> for Kleisli<'a, A, B, E> {
+    extern "rust-call" fn call(&self, (x,): (A,)) -> Result<B, E> {
+        self.run(x)
+    }
+}
+
 #[cfg(test)]
 mod tests
 {
-    use super::Morphism;
+    use super::{Morphism, Step, loop_while};
+
+    #[test]
+    fn loop_while_trampolines() {
+        // count down to zero, accumulating the sum of the steps taken
+        let countdown = loop_while(
+            Morphism::new::<(uint, uint)>().tail(|(x, acc): (uint, uint)| {
+                if x == 0u { Step::Done(acc) } else { Step::More((x - 1u, acc + x)) }
+            })
+        );
+
+        assert_eq!(countdown((5u, 0u)), 15u);
+        assert_eq!(countdown((0u, 0u)), 0u);
+    }
+
+    #[test]
+    fn product_combinators() {
+        let inc = Morphism::new::<uint>().tail(|x| x + 1u);
+        let show = Morphism::new::<uint>().tail(|x| x.to_string());
+
+        let first = Morphism::new::<uint>().tail(|x| x + 1u).first::<bool>();
+        assert_eq!(first((41u, true)), (42u, true));
+
+        let second = Morphism::new::<uint>().tail(|x| x + 1u).second::<bool>();
+        assert_eq!(second((true, 41u)), (true, 42u));
+
+        let fanned = inc.fanout(show);
+        assert_eq!(fanned(41u), (42u, String::from_str("41")));
+
+        let paired = Morphism::new::<uint>().tail(|x| x + 1u)
+            .product(Morphism::new::<bool>().tail(|x: bool| !x));
+        assert_eq!(paired((41u, true)), (42u, false));
+    }
+
+    #[test]
+    fn kleisli_short_circuits() {
+        let f = Morphism::new_kleisli::<uint, String>()
+            .and_then(|x| if x < 10u { Ok(x + 1u) } else { Err("stage1".to_string()) })
+            .and_then(|x| if x < 10u { Ok(x + 1u) } else { Err("stage2".to_string()) })
+            .and_then(|x| if x < 10u { Ok(x + 1u) } else { Err("stage3".to_string()) });
+
+        assert_eq!(f(0u), Ok(3u));
+        // stage1 passes 9 through as 10, so stage2 is the one that fails;
+        // stage3 never runs, so the error can't be mistaken for its own.
+        assert_eq!(f(9u), Err("stage2".to_string()));
+    }
+
+    #[test]
+    fn mutable_state() {
+        let mut calls = 0u;
+        let mut total = 0u;
+        {
+            let mut f = Morphism::new_mut::<uint>()
+                .tail_mut(|x| { calls += 1; x })
+                .tail_mut(|x| { total += x; total });
+
+            assert_eq!(f(1u), 1u);
+            assert_eq!(f(2u), 3u);
+            assert_eq!(f(3u), 6u);
+        } // f's closures release their borrows of calls and total here
+        assert_eq!(calls, 3u);
+    }
+
+    #[test]
+    fn then_mut_composes() {
+        let mut calls = 0u;
+        let f = Morphism::new_mut::<uint>()
+            .tail_mut(|x| { calls += 1; x + calls });
+
+        let mut total = 0u;
+        let g = Morphism::new_mut::<uint>()
+            .tail_mut(|x| { total += x; total });
+
+        let mut h = f.then_mut(g);
+        assert_eq!(h(0u), 1u);
+        assert_eq!(h(0u), 3u);
+        assert_eq!(h(0u), 6u);
+    }
 
     #[test]
     fn readme() {